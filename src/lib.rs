@@ -0,0 +1,48 @@
+#![no_std]
+
+#[cfg(any(test, feature = "alloc"))]
+extern crate alloc;
+
+mod managed;
+pub mod socket;
+
+pub use managed::Managed;
+
+use core::fmt;
+
+/// The error type for the networking stack.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Error {
+    /// An operation cannot proceed because a buffer is empty or full.
+    ///
+    /// This is usually a temporary condition: retrying the operation later, once
+    /// the application has drained or refilled the buffer, may succeed.
+    Exhausted,
+    /// An incoming packet could not be parsed, or an outgoing packet could not be
+    /// emitted, because a field contained an invalid value.
+    Malformed,
+    /// An operation cannot proceed because a required address or port is missing
+    /// or is the unspecified value.
+    Unaddressable,
+    /// A received packet could only be partially copied into the provided buffer
+    /// because the buffer was smaller than the packet.
+    Truncated,
+    /// An incoming packet was recognized, but did not match the receiver it was
+    /// delivered to, and was silently dropped.
+    Rejected
+}
+
+/// The result type for the networking stack.
+pub type Result<T> = core::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &Error::Exhausted     => write!(f, "buffer space exhausted"),
+            &Error::Malformed     => write!(f, "malformed packet"),
+            &Error::Unaddressable => write!(f, "unaddressable destination"),
+            &Error::Truncated     => write!(f, "truncated packet"),
+            &Error::Rejected      => write!(f, "packet rejected")
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use core::ops::{Deref, DerefMut};
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// A managed slice.
+///
+/// This enum can contain either a borrowed slice, or (if the "alloc" feature is enabled)
+/// an owned slice, held as a `Box`. It is used to avoid a dependency on `alloc` wherever
+/// possible, while allowing hosted environments to hand over ownership of dynamically
+/// allocated memory.
+pub enum Managed<'a, T: 'a + ?Sized> {
+    Borrowed(&'a mut T),
+    #[cfg(feature = "alloc")]
+    Owned(Box<T>)
+}
+
+impl<'a, T: ?Sized> From<&'a mut T> for Managed<'a, T> {
+    fn from(value: &'a mut T) -> Self {
+        Managed::Borrowed(value)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, T> From<Box<T>> for Managed<'a, T> where T: ?Sized {
+    fn from(value: Box<T>) -> Self {
+        Managed::Owned(value)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, T> From<Vec<T>> for Managed<'a, [T]> {
+    fn from(value: Vec<T>) -> Self {
+        Managed::Owned(value.into_boxed_slice())
+    }
+}
+
+impl<'a, T: ?Sized> Deref for Managed<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        match self {
+            &Managed::Borrowed(ref value) => value,
+            #[cfg(feature = "alloc")]
+            &Managed::Owned(ref value)    => value
+        }
+    }
+}
+
+impl<'a, T: ?Sized> DerefMut for Managed<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        match self {
+            &mut Managed::Borrowed(ref mut value) => value,
+            #[cfg(feature = "alloc")]
+            &mut Managed::Owned(ref mut value)    => value
+        }
+    }
+}
@@ -0,0 +1,38 @@
+pub mod udp;
+mod set;
+
+pub use self::udp::UdpSocket;
+pub use self::set::{Set as SocketSet, Handle as SocketHandle, IterMut as SocketSetIterMut};
+
+/// A network socket.
+///
+/// This enum abstracts over the various kinds of sockets the networking stack
+/// understands, so a `SocketSet` can hold a heterogeneous collection of them
+/// without the interface's type signature depending on how many sockets of
+/// each kind are in use.
+pub enum Socket<'a> {
+    Udp(UdpSocket<'a>),
+    #[doc(hidden)]
+    __Nonexhaustive
+}
+
+/// A conversion trait for extracting a concrete socket type out of a `Socket`.
+///
+/// `Socket` implements this trait for every concrete socket type, so application
+/// code can recover one from a `Socket` without matching by hand:
+///
+/// ```ignore
+/// let udp: &mut UdpSocket = sockets.get_mut(handle).as_socket();
+/// ```
+pub trait AsSocket<T> {
+    fn as_socket(&mut self) -> &mut T;
+}
+
+impl<'a> AsSocket<UdpSocket<'a>> for Socket<'a> {
+    fn as_socket(&mut self) -> &mut UdpSocket<'a> {
+        match self {
+            &mut Socket::Udp(ref mut socket) => socket,
+            _ => panic!("as_socket() called with a mismatched socket type")
+        }
+    }
+}
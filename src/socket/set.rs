@@ -0,0 +1,88 @@
+use core::slice;
+
+use Managed;
+use super::Socket;
+
+/// An opaque handle identifying a socket in a `Set`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Handle(usize);
+
+/// A collection of sockets, indexed by opaque handles.
+///
+/// The interface's poll loop drives every socket in a `Set` without needing to
+/// know the number or kind of sockets it holds ahead of time.
+pub struct Set<'a, 'b: 'a> {
+    sockets: Managed<'b, [Option<Socket<'a>>]>
+}
+
+impl<'a, 'b: 'a> Set<'a, 'b> {
+    /// Create a socket set using the given storage.
+    pub fn new<T>(sockets: T) -> Set<'a, 'b>
+            where T: Into<Managed<'b, [Option<Socket<'a>>]>> {
+        Set { sockets: sockets.into() }
+    }
+
+    /// Add a socket to the set, and return its handle.
+    ///
+    /// # Panics
+    /// This function panics if the set has no free slot.
+    pub fn add(&mut self, socket: Socket<'a>) -> Handle {
+        for (index, slot) in self.sockets.iter_mut().enumerate() {
+            if slot.is_none() {
+                *slot = Some(socket);
+                return Handle(index)
+            }
+        }
+        panic!("adding a socket to a full SocketSet")
+    }
+
+    /// Get a reference to the socket identified by `handle`.
+    ///
+    /// # Panics
+    /// This function panics if `handle` refers to an empty slot.
+    pub fn get(&self, handle: Handle) -> &Socket<'a> {
+        self.sockets[handle.0].as_ref()
+            .expect("handle refers to an empty slot")
+    }
+
+    /// Get a mutable reference to the socket identified by `handle`.
+    ///
+    /// # Panics
+    /// This function panics if `handle` refers to an empty slot.
+    pub fn get_mut(&mut self, handle: Handle) -> &mut Socket<'a> {
+        self.sockets[handle.0].as_mut()
+            .expect("handle refers to an empty slot")
+    }
+
+    /// Remove the socket identified by `handle`, freeing its slot.
+    ///
+    /// # Panics
+    /// This function panics if `handle` refers to an empty slot.
+    pub fn remove(&mut self, handle: Handle) -> Socket<'a> {
+        self.sockets[handle.0].take()
+            .expect("handle refers to an empty slot")
+    }
+
+    /// Iterate over every occupied slot in this set, in handle order.
+    pub fn iter_mut<'c>(&'c mut self) -> IterMut<'c, 'a> {
+        IterMut { inner: self.sockets.iter_mut() }
+    }
+}
+
+/// A mutable iterator over the sockets in a `Set`, obtained from `Set::iter_mut`.
+pub struct IterMut<'c, 'a: 'c> {
+    inner: slice::IterMut<'c, Option<Socket<'a>>>
+}
+
+impl<'c, 'a: 'c> Iterator for IterMut<'c, 'a> {
+    type Item = &'c mut Socket<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(slot) = self.inner.next() {
+            if let Some(ref mut socket) = *slot {
+                return Some(socket)
+            }
+        }
+        None
+    }
+}
@@ -1,6 +1,8 @@
-use core::borrow::BorrowMut;
+use core::cmp;
 
 use Error;
+use Managed;
+use Result;
 use wire::{InternetAddress as Address, InternetEndpoint as Endpoint};
 use wire::UdpRepr;
 
@@ -14,15 +16,22 @@ pub trait Buffer {
     /// This function allocates a sequence of octets the given size and associates
     /// the given endpoint with it, then calls `f`; if the buffer is full, it
     /// returns `Err(Error::Exhausted)` instead.
-    fn enqueue<R, F>(&mut self, endpoint: Endpoint, size: usize, f: F) -> Result<R, Error>
-        where F: FnOnce(&mut [u8]) -> Result<R, Error>;
+    fn enqueue<R, F>(&mut self, endpoint: Endpoint, size: usize, f: F) -> Result<R>
+        where F: FnOnce(&mut [u8]) -> Result<R>;
 
     /// Dequeue a packet.
     ///
     /// This function calls `f` with the oldest enqueued packet; if the buffer is empty,
     /// it returns `Err(Error::Exhausted)` instead.
-    fn dequeue<R, F>(&mut self, f: F) -> Result<R, Error>
-        where F: FnOnce(Endpoint, &[u8]) -> Result<R, Error>;
+    fn dequeue<R, F>(&mut self, f: F) -> Result<R>
+        where F: FnOnce(Endpoint, &[u8]) -> Result<R>;
+
+    /// Peek at the oldest enqueued packet, without dequeuing it.
+    ///
+    /// This function calls `f` with the oldest enqueued packet; if the buffer is empty,
+    /// it returns `Err(Error::Exhausted)` instead.
+    fn peek<R, F>(&mut self, f: F) -> Result<R>
+        where F: FnOnce(Endpoint, &[u8]) -> Result<R>;
 }
 
 /// A packet buffer that does not have any storage.
@@ -38,13 +47,18 @@ impl NullBuffer {
 }
 
 impl Buffer for NullBuffer {
-    fn enqueue<R, F>(&mut self, _endpoint: Endpoint, _size: usize, _f: F) -> Result<R, Error>
-            where F: FnOnce(&mut [u8]) -> Result<R, Error> {
+    fn enqueue<R, F>(&mut self, _endpoint: Endpoint, _size: usize, _f: F) -> Result<R>
+            where F: FnOnce(&mut [u8]) -> Result<R> {
         Err(Error::Exhausted)
     }
 
-    fn dequeue<R, F>(&mut self, _f: F) -> Result<R, Error>
-            where F: FnOnce(Endpoint, &[u8]) -> Result<R, Error> {
+    fn dequeue<R, F>(&mut self, _f: F) -> Result<R>
+            where F: FnOnce(Endpoint, &[u8]) -> Result<R> {
+        Err(Error::Exhausted)
+    }
+
+    fn peek<R, F>(&mut self, _f: F) -> Result<R>
+            where F: FnOnce(Endpoint, &[u8]) -> Result<R> {
         Err(Error::Exhausted)
     }
 }
@@ -54,33 +68,34 @@ impl Buffer for NullBuffer {
 /// The unitary buffer uses a provided slice to store no more than one packet at any time.
 /// If there is an enqueued packet, or if the requested size is larger than the storage size,
 /// the unitary rejects the enqueue operation with `Error::Exhausted`.
-pub struct UnitaryBuffer<T: BorrowMut<[u8]>> {
+pub struct UnitaryBuffer<'a> {
     endpoint: Endpoint,
-    storage:  T,
+    storage:  Managed<'a, [u8]>,
     size:     usize
 }
 
-impl<T: BorrowMut<[u8]>> UnitaryBuffer<T> {
+impl<'a> UnitaryBuffer<'a> {
     /// Create an unitary packet buffer, using the given storage.
-    pub fn new(storage: T) -> UnitaryBuffer<T> {
+    pub fn new<T>(storage: T) -> UnitaryBuffer<'a>
+            where T: Into<Managed<'a, [u8]>> {
         UnitaryBuffer {
             endpoint: Default::default(),
-            storage:  storage,
+            storage:  storage.into(),
             size:     0
         }
     }
 }
 
-impl<T: BorrowMut<[u8]>> Buffer for UnitaryBuffer<T> {
-    fn enqueue<R, F>(&mut self, endpoint: Endpoint, size: usize, f: F) -> Result<R, Error>
-            where F: FnOnce(&mut [u8]) -> Result<R, Error> {
-        let mut storage = self.storage.borrow_mut();
+impl<'a> Buffer for UnitaryBuffer<'a> {
+    fn enqueue<R, F>(&mut self, endpoint: Endpoint, size: usize, f: F) -> Result<R>
+            where F: FnOnce(&mut [u8]) -> Result<R> {
         match self.endpoint {
             Endpoint { addr: Address::Invalid, .. }
-                    if size <= storage.len() => {
+                    if size <= self.storage.len() => {
                 // If `f` fails, don't enqueue the packet.
-                let result = try!(f(&mut storage[..size]));
+                let result = f(&mut self.storage[..size])?;
                 self.endpoint = endpoint;
+                self.size = size;
                 Ok(result)
             },
             _ => {
@@ -89,21 +104,133 @@ impl<T: BorrowMut<[u8]>> Buffer for UnitaryBuffer<T> {
         }
     }
 
-    fn dequeue<R, F>(&mut self, f: F) -> Result<R, Error>
-            where F: FnOnce(Endpoint, &[u8]) -> Result<R, Error> {
-        let mut storage = self.storage.borrow_mut();
+    fn dequeue<R, F>(&mut self, f: F) -> Result<R>
+            where F: FnOnce(Endpoint, &[u8]) -> Result<R> {
         match self.endpoint {
             Endpoint { addr: Address::Invalid, .. } => {
                 Err(Error::Exhausted)
             },
             _ => {
                 // If `f` fails, still dequeue the packet.
-                let result = f(self.endpoint, &storage[..self.size]);
+                let result = f(self.endpoint, &self.storage[..self.size]);
                 self.endpoint = Default::default();
                 result
             }
         }
     }
+
+    fn peek<R, F>(&mut self, f: F) -> Result<R>
+            where F: FnOnce(Endpoint, &[u8]) -> Result<R> {
+        match self.endpoint {
+            Endpoint { addr: Address::Invalid, .. } => {
+                Err(Error::Exhausted)
+            },
+            _ => {
+                f(self.endpoint, &self.storage[..self.size])
+            }
+        }
+    }
+}
+
+/// A single packet slot used by the ring buffer.
+///
+/// Each element owns its own payload storage, so the size of a ring buffer slot
+/// bounds the largest packet that can be enqueued into it.
+pub struct PacketElem<'a> {
+    endpoint: Endpoint,
+    size:     usize,
+    payload:  Managed<'a, [u8]>
+}
+
+impl<'a> PacketElem<'a> {
+    /// Create an empty packet slot, using the given storage.
+    pub fn new<T>(payload: T) -> PacketElem<'a>
+            where T: Into<Managed<'a, [u8]>> {
+        PacketElem {
+            endpoint: Default::default(),
+            size:     0,
+            payload:  payload.into()
+        }
+    }
+}
+
+/// A packet buffer that stores up to the number of packets given by the length
+/// of its backing storage.
+///
+/// The ring buffer uses a provided slice of packet slots to store, in FIFO order,
+/// as many packets as there are slots. If there is no free slot, or if the requested
+/// size is larger than a single slot's storage, the ring buffer rejects the enqueue
+/// operation with `Error::Exhausted`.
+pub struct RingBuffer<'a> {
+    storage: Managed<'a, [PacketElem<'a>]>,
+    read_at: usize,
+    length:  usize
+}
+
+impl<'a> RingBuffer<'a> {
+    /// Create a ring packet buffer, using the given storage of packet slots.
+    pub fn new<T>(storage: T) -> RingBuffer<'a>
+            where T: Into<Managed<'a, [PacketElem<'a>]>> {
+        RingBuffer {
+            storage: storage.into(),
+            read_at: 0,
+            length:  0
+        }
+    }
+
+    fn mask(&self, index: usize) -> usize {
+        index % self.storage.len()
+    }
+
+    fn incr(&self, index: usize) -> usize {
+        self.mask(index + 1)
+    }
+
+    fn empty(&self) -> bool {
+        self.length == 0
+    }
+
+    fn full(&self) -> bool {
+        self.length == self.storage.len()
+    }
+}
+
+impl<'a> Buffer for RingBuffer<'a> {
+    fn enqueue<R, F>(&mut self, endpoint: Endpoint, size: usize, f: F) -> Result<R>
+            where F: FnOnce(&mut [u8]) -> Result<R> {
+        if self.full() { return Err(Error::Exhausted) }
+        let index = self.mask(self.read_at + self.length);
+
+        let elem = &mut self.storage[index];
+        if size > elem.payload.len() { return Err(Error::Exhausted) }
+
+        // If `f` fails, don't enqueue the packet.
+        let result = f(&mut elem.payload[..size])?;
+        elem.endpoint = endpoint;
+        elem.size = size;
+        self.length += 1;
+        Ok(result)
+    }
+
+    fn dequeue<R, F>(&mut self, f: F) -> Result<R>
+            where F: FnOnce(Endpoint, &[u8]) -> Result<R> {
+        if self.empty() { return Err(Error::Exhausted) }
+        let index = self.read_at;
+
+        let elem = &mut self.storage[index];
+        // If `f` fails, still dequeue the packet.
+        let result = f(elem.endpoint, &elem.payload[..elem.size]);
+        self.read_at = self.incr(self.read_at);
+        self.length -= 1;
+        result
+    }
+
+    fn peek<R, F>(&mut self, f: F) -> Result<R>
+            where F: FnOnce(Endpoint, &[u8]) -> Result<R> {
+        if self.empty() { return Err(Error::Exhausted) }
+        let elem = &mut self.storage[self.read_at];
+        f(elem.endpoint, &elem.payload[..elem.size])
+    }
 }
 
 /// An User Datagram Protocol socket.
@@ -125,9 +252,44 @@ impl<RxBufferT: Buffer, TxBufferT: Buffer> Socket<RxBufferT, TxBufferT> {
         }
     }
 
+    /// Bind the socket to the given endpoint.
+    ///
+    /// This function returns `Err(Error::Unaddressable)` if the port in the given
+    /// endpoint is zero. The address may be left unspecified, in which case the
+    /// socket accepts packets sent to any of the interface's addresses.
+    pub fn bind<T: Into<Endpoint>>(&mut self, endpoint: T) -> Result<()> {
+        let endpoint = endpoint.into();
+        if endpoint.port == 0 { return Err(Error::Unaddressable) }
+
+        self.endpoint = endpoint;
+        Ok(())
+    }
+
+    /// Return the bound endpoint.
+    pub fn endpoint(&self) -> Endpoint {
+        self.endpoint
+    }
+
+    /// Return whether the socket is bound to a specified address, as opposed to
+    /// the unspecified address.
+    pub fn is_specified(&self) -> bool {
+        !self.endpoint.addr.is_unspecified()
+    }
+
+    /// Return whether the socket is open, i.e. bound to a nonzero port.
+    pub fn is_open(&self) -> bool {
+        self.endpoint.port != 0
+    }
+
     /// Send a packet to a remote endpoint, without copying.
-    pub fn send<R, F>(&mut self, endpoint: Endpoint, size: usize, f: F) -> Result<R, Error>
-            where F: FnOnce(&mut [u8]) -> Result<R, Error> {
+    ///
+    /// This function returns `Err(Error::Unaddressable)` if the remote endpoint has
+    /// an unspecified address or a zero port.
+    pub fn send<R, F>(&mut self, endpoint: Endpoint, size: usize, f: F) -> Result<R>
+            where F: FnOnce(&mut [u8]) -> Result<R> {
+        if endpoint.port == 0 || endpoint.addr.is_unspecified() {
+            return Err(Error::Unaddressable)
+        }
         self.tx_buffer.enqueue(endpoint, size, f)
     }
 
@@ -135,27 +297,39 @@ impl<RxBufferT: Buffer, TxBufferT: Buffer> Socket<RxBufferT, TxBufferT> {
     ///
     /// This function returns `Err(Error::Exhausted)` if the slice is larger than the internal
     /// buffer can accomodate.
-    pub fn send_slice(&mut self, endpoint: Endpoint, data: &[u8]) -> Result<(), Error> {
-        self.tx_buffer.enqueue(endpoint, data.len(), |buffer| {
+    pub fn send_slice(&mut self, endpoint: Endpoint, data: &[u8]) -> Result<()> {
+        self.send(endpoint, data.len(), |buffer| {
             Ok(buffer.copy_from_slice(data))
         })
     }
 
     /// Receive a packet from a remote endpoint, without copying.
-    pub fn recv<R, F>(&mut self, f: F) -> Result<R, Error>
-            where F: FnOnce(Endpoint, &[u8]) -> Result<R, Error> {
+    pub fn recv<R, F>(&mut self, f: F) -> Result<R>
+            where F: FnOnce(Endpoint, &[u8]) -> Result<R> {
         self.rx_buffer.dequeue(f)
     }
 
-    /// Receive a packet from a remote endpoint, copying the given slice to the internal buffer.
+    /// Peek at a packet from a remote endpoint, without copying or dequeuing it.
+    ///
+    /// This is useful for sizing a caller-provided buffer before calling `recv_slice`.
+    pub fn peek<R, F>(&mut self, f: F) -> Result<R>
+            where F: FnOnce(Endpoint, &[u8]) -> Result<R> {
+        self.rx_buffer.peek(f)
+    }
+
+    /// Receive a packet from a remote endpoint, copying it into the given slice.
     ///
-    /// This function returns `Err(Error::Exhausted)` if the slice is smaller than the packet
-    /// queued in the internal buffer.
-    pub fn recv_slice(&mut self, data: &mut [u8]) -> Result<(usize, Endpoint), Error> {
+    /// This function always dequeues the oldest packet, even if `data` is smaller
+    /// than it; in that case, as many bytes as fit are copied, and the returned
+    /// length reflects only the copied portion. The returned datagram length is
+    /// the full, un-truncated size of the packet, so a caller that sees it differ
+    /// from the returned copied length knows the packet was truncated.
+    pub fn recv_slice(&mut self, data: &mut [u8]) -> Result<(usize, usize, Endpoint)> {
         self.rx_buffer.dequeue(|endpoint, buffer| {
-            if data.len() < buffer.len() { return Err(Error::Exhausted) }
-            data[..buffer.len()].copy_from_slice(buffer);
-            Ok((buffer.len(), endpoint))
+            let length = buffer.len();
+            let copy_len = cmp::min(data.len(), length);
+            data[..copy_len].copy_from_slice(&buffer[..copy_len]);
+            Ok((copy_len, length, endpoint))
         })
     }
 
@@ -166,7 +340,7 @@ impl<RxBufferT: Buffer, TxBufferT: Buffer> Socket<RxBufferT, TxBufferT> {
     ///
     /// This function is used internally by the networking stack.
     pub fn collect<'a>(&mut self, src_addr: Address, dst_addr: Address,
-                       repr: &UdpRepr<'a>) -> Result<(), Error> {
+                       repr: &UdpRepr<'a>) -> Result<()> {
         if repr.dst_port != self.endpoint.port { return Err(Error::Rejected) }
         if !self.endpoint.addr.is_unspecified() {
             if self.endpoint.addr != dst_addr { return Err(Error::Rejected) }
@@ -184,8 +358,8 @@ impl<RxBufferT: Buffer, TxBufferT: Buffer> Socket<RxBufferT, TxBufferT> {
     /// `Err(Error::Exhausted)` is returned.
     ///
     /// This function is used internally by the networking stack.
-    pub fn dispatch<R, F>(&mut self, f: F) -> Result<R, Error>
-            where F: for<'a> FnOnce(Address, Address, &UdpRepr<'a>) -> Result<R, Error> {
+    pub fn dispatch<R, F>(&mut self, f: F) -> Result<R>
+            where F: for<'a> FnOnce(Address, Address, &UdpRepr<'a>) -> Result<R> {
         let src_endpoint = self.endpoint;
         self.tx_buffer.dequeue(|dst_endpoint, buffer| {
             f(src_endpoint.addr, dst_endpoint.addr, &UdpRepr {
@@ -195,4 +369,10 @@ impl<RxBufferT: Buffer, TxBufferT: Buffer> Socket<RxBufferT, TxBufferT> {
             })
         })
     }
-}
\ No newline at end of file
+}
+
+/// The concrete UDP socket type stored in a `SocketSet`.
+///
+/// Application code that wants a different buffer implementation can still
+/// use `Socket<RxBufferT, TxBufferT>` directly.
+pub type UdpSocket<'a> = Socket<RingBuffer<'a>, RingBuffer<'a>>;